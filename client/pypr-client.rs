@@ -1,7 +1,10 @@
 use std::env;
-use std::io::{Read, Write};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::os::unix::net::UnixStream;
+use std::path::Path;
 use std::process::exit;
+use std::time::SystemTime;
 
 // Exit codes matching pyprland/models.py ExitCode
 const EXIT_SUCCESS: i32 = 0;
@@ -10,51 +13,236 @@ const EXIT_ENV_ERROR: i32 = 2;
 const EXIT_CONNECTION_ERROR: i32 = 3;
 const EXIT_COMMAND_ERROR: i32 = 4;
 
-fn run() -> Result<(), i32> {
-    // Collect arguments (skip program name)
-    let args: Vec<String> = env::args().skip(1).collect();
+// Older Hyprland releases placed the IPC socket under /tmp/hypr/{sig}/ instead
+// of $XDG_RUNTIME_DIR/hypr/{sig}/. Probe both locations and use whichever
+// actually exists, preferring the XDG one.
+//
+// Returns `None` only when neither location is even addressable (no
+// `XDG_RUNTIME_DIR`), which is a genuine environment error. If
+// `XDG_RUNTIME_DIR` is set but no socket exists at either candidate, fall
+// back to the XDG path anyway so the caller's `UnixStream::connect` is what
+// fails — surfacing "daemon not running" (EXIT_CONNECTION_ERROR) instead of
+// misreporting the environment as unset.
+fn find_socket(signature: &str) -> Option<String> {
+    let xdg_candidate = env::var("XDG_RUNTIME_DIR")
+        .ok()
+        .map(|runtime_dir| format!("{}/hypr/{}/.pyprland.sock", runtime_dir, signature));
+    let tmp_candidate = format!("/tmp/hypr/{}/.pyprland.sock", signature);
 
-    if args.is_empty() {
-        eprintln!("No command provided.");
-        eprintln!("Usage: pypr <command> [args...]");
-        eprintln!("Try 'pypr help' for available commands.");
-        return Err(EXIT_USAGE_ERROR);
+    if let Some(path) = &xdg_candidate {
+        if Path::new(path).exists() {
+            return Some(path.clone());
+        }
+    }
+    if Path::new(&tmp_candidate).exists() {
+        return Some(tmp_candidate);
     }
 
-    // Build command message
-    let message = format!("{}\n", args.join(" "));
+    xdg_candidate
+}
+
+// A Hyprland instance discovered under $XDG_RUNTIME_DIR/hypr/.
+struct Instance {
+    signature: String,
+    mtime: SystemTime,
+    pid: Option<String>,
+}
 
-    if message.len() > 1024 {
-        eprintln!("Error: Command too long (max 1022 characters).");
-        return Err(EXIT_USAGE_ERROR);
+// Enumerate the instance directories under $XDG_RUNTIME_DIR/hypr/, newest first.
+// This mirrors how `hyprctl -i` resolves instances by index.
+fn list_instances(runtime_dir: &str) -> Vec<Instance> {
+    let hypr_dir = format!("{}/hypr", runtime_dir);
+    let mut instances: Vec<Instance> = fs::read_dir(&hypr_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let mtime = entry.metadata().ok()?.modified().ok()?;
+            let signature = entry.file_name().to_string_lossy().into_owned();
+            // Hyprland writes its pid as the first line of hyprland.lock.
+            let pid = fs::read_to_string(entry.path().join("hyprland.lock"))
+                .ok()
+                .and_then(|contents| contents.lines().next().map(|line| line.trim().to_string()));
+            Some(Instance {
+                signature,
+                mtime,
+                pid,
+            })
+        })
+        .collect();
+    instances.sort_by_key(|instance| std::cmp::Reverse(instance.mtime));
+    instances
+}
+
+// Resolve the `-i`/`--instance` selector (a signature or a numeric index into
+// the newest-first instance list) down to a signature string. With no
+// selector, falls back to $HYPRLAND_INSTANCE_SIGNATURE.
+fn resolve_signature(selector: Option<&str>) -> Result<String, i32> {
+    match selector {
+        Some(value) => {
+            if let Ok(index) = value.parse::<usize>() {
+                let runtime_dir = env::var("XDG_RUNTIME_DIR").map_err(|_| {
+                    eprintln!("Environment error: XDG_RUNTIME_DIR not set.");
+                    EXIT_ENV_ERROR
+                })?;
+                let instances = list_instances(&runtime_dir);
+                instances
+                    .into_iter()
+                    .nth(index)
+                    .map(|instance| instance.signature)
+                    .ok_or_else(|| {
+                        eprintln!("Error: No instance at index {}.", index);
+                        EXIT_ENV_ERROR
+                    })
+            } else {
+                Ok(value.to_string())
+            }
+        }
+        None => env::var("HYPRLAND_INSTANCE_SIGNATURE").map_err(|_| {
+            eprintln!("Environment error: XDG_RUNTIME_DIR or HYPRLAND_INSTANCE_SIGNATURE not set.");
+            eprintln!("Are you running under Hyprland?");
+            EXIT_ENV_ERROR
+        }),
     }
+}
 
-    // Get socket path from environment
+fn print_instances() -> Result<(), i32> {
     let runtime_dir = env::var("XDG_RUNTIME_DIR").map_err(|_| {
-        eprintln!("Environment error: XDG_RUNTIME_DIR or HYPRLAND_INSTANCE_SIGNATURE not set.");
-        eprintln!("Are you running under Hyprland?");
+        eprintln!("Environment error: XDG_RUNTIME_DIR not set.");
         EXIT_ENV_ERROR
     })?;
+    for (index, instance) in list_instances(&runtime_dir).into_iter().enumerate() {
+        let pid = instance.pid.as_deref().unwrap_or("?");
+        let since = instance
+            .mtime
+            .elapsed()
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        println!(
+            "{}: {} (pid {}, {}s ago)",
+            index, instance.signature, pid, since
+        );
+    }
+    Ok(())
+}
+
+// Resolve the instance signature and connect to its socket.
+fn connect(instance_selector: Option<&str>) -> Result<UnixStream, i32> {
+    let signature = resolve_signature(instance_selector)?;
 
-    let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE").map_err(|_| {
+    let socket_path = find_socket(&signature).ok_or_else(|| {
         eprintln!("Environment error: XDG_RUNTIME_DIR or HYPRLAND_INSTANCE_SIGNATURE not set.");
         eprintln!("Are you running under Hyprland?");
         EXIT_ENV_ERROR
     })?;
 
-    let socket_path = format!("{}/hypr/{}/.pyprland.sock", runtime_dir, signature);
-
     if socket_path.len() >= 256 {
         eprintln!("Error: Socket path too long (max 255 characters).");
         return Err(EXIT_ENV_ERROR);
     }
 
-    // Connect to Unix socket
-    let mut stream = UnixStream::connect(&socket_path).map_err(|_| {
+    UnixStream::connect(&socket_path).map_err(|_| {
         eprintln!("Cannot connect to pyprland daemon at {}.", socket_path);
         eprintln!("Is the daemon running? Start it with: pypr (no arguments)");
         EXIT_CONNECTION_ERROR
-    })?;
+    })
+}
+
+fn run() -> Result<(), i32> {
+    // Collect arguments (skip program name)
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // Pull out a global `-i`/`--instance <sig-or-index>` flag, wherever it
+    // appears, before looking at the rest as the command.
+    let mut instance_selector: Option<String> = None;
+    if let Some(pos) = args
+        .iter()
+        .position(|a| a == "-i" || a == "--instance")
+    {
+        if pos + 1 >= args.len() {
+            eprintln!("Error: {} requires a value.", args[pos]);
+            return Err(EXIT_USAGE_ERROR);
+        }
+        instance_selector = Some(args.remove(pos + 1));
+        args.remove(pos);
+    }
+
+    // Pull out the `--batch` flag; its remaining args are a `;`-separated
+    // list of commands rather than a single one.
+    let batch = if let Some(pos) = args.iter().position(|a| a == "--batch") {
+        args.remove(pos);
+        true
+    } else {
+        false
+    };
+
+    // Pull out the `-j`/`--json` flag, for structured stdout output.
+    let json = if let Some(pos) = args.iter().position(|a| a == "-j" || a == "--json") {
+        args.remove(pos);
+        true
+    } else {
+        false
+    };
+
+    if args.is_empty() {
+        eprintln!("No command provided.");
+        eprintln!("Usage: pypr <command> [args...]");
+        eprintln!("Try 'pypr help' for available commands.");
+        return Err(EXIT_USAGE_ERROR);
+    }
+
+    if args[0] == "instances" {
+        return print_instances();
+    }
+
+    if args[0] == "subscribe" {
+        let filters = args[1..].to_vec();
+        let mut stream = connect(instance_selector.as_deref())?;
+
+        let subscribe_msg = if filters.is_empty() {
+            "subscribe\n".to_string()
+        } else {
+            format!("subscribe {}\n", filters.join(" "))
+        };
+        stream.write_all(subscribe_msg.as_bytes()).map_err(|_| {
+            eprintln!("Error: Failed to send command to daemon.");
+            EXIT_CONNECTION_ERROR
+        })?;
+
+        return run_subscribe(&mut stream, &filters);
+    }
+
+    let segments: Vec<String> = if batch {
+        args.join(" ")
+            .split(';')
+            .map(|segment| segment.trim().to_string())
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    } else {
+        vec![args.join(" ")]
+    };
+
+    if segments.is_empty() {
+        eprintln!("No command provided.");
+        return Err(EXIT_USAGE_ERROR);
+    }
+
+    // The 1022-char limit applies per command, not to the whole batch.
+    for segment in &segments {
+        if segment.len() + 1 > 1024 {
+            eprintln!("Error: Command too long (max 1022 characters).");
+            return Err(EXIT_USAGE_ERROR);
+        }
+    }
+
+    if batch {
+        return run_batch(instance_selector.as_deref(), &segments, json);
+    }
+
+    let mut stream = connect(instance_selector.as_deref())?;
+
+    let message = format!("{}\n", segments[0]);
 
     // Send command
     stream.write_all(message.as_bytes()).map_err(|_| {
@@ -75,7 +263,16 @@ fn run() -> Result<(), i32> {
         EXIT_CONNECTION_ERROR
     })?;
 
-    // Parse response and determine exit code
+    handle_response(&response, json)
+}
+
+// Parse a single daemon response and print its payload, returning the exit
+// code the response maps to.
+fn handle_response(response: &str, json: bool) -> Result<(), i32> {
+    if json {
+        return handle_response_json(response);
+    }
+
     if let Some(error_msg) = response.strip_prefix("ERROR: ") {
         eprintln!("Error: {}", error_msg.trim_end());
         Err(EXIT_COMMAND_ERROR)
@@ -95,6 +292,124 @@ fn run() -> Result<(), i32> {
     }
 }
 
+// Escape a string for embedding in a JSON string literal. Hand-rolled since
+// this client has no JSON crate dependency.
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Same response parsing as `handle_response`, but emits a single JSON object
+// on stdout instead of printing raw text, following hyprctl's `-j` convention.
+fn handle_response_json(response: &str) -> Result<(), i32> {
+    if let Some(error_msg) = response.strip_prefix("ERROR: ") {
+        println!(
+            "{{\"status\":\"error\",\"message\":\"{}\"}}",
+            json_escape(error_msg.trim_end())
+        );
+        Err(EXIT_COMMAND_ERROR)
+    } else if let Some(rest) = response.strip_prefix("OK") {
+        let output = rest.trim_start().trim_end_matches('\n');
+        println!(
+            "{{\"status\":\"ok\",\"output\":\"{}\"}}",
+            json_escape(output)
+        );
+        Ok(())
+    } else {
+        // Legacy response (version, help, dumpjson) - wrap as-is.
+        println!(
+            "{{\"status\":\"ok\",\"output\":\"{}\"}}",
+            json_escape(response.trim_end_matches('\n'))
+        );
+        Ok(())
+    }
+}
+
+// Run several `;`-separated commands, one per connection, from a single
+// `pypr --batch` invocation instead of spawning a fresh process per command.
+//
+// Responses aren't framed on the wire (the daemon just writes until it's
+// done and the baseline client reads to EOF), so a multi-line reply like
+// `version`/`help`/`dumpjson` can't be told apart from the start of the next
+// command's response on a shared connection with `read_line`. Reconnecting
+// per command keeps each response's `read_to_string` bounded by its own
+// socket EOF, same as the non-batch path.
+fn run_batch(instance_selector: Option<&str>, segments: &[String], json: bool) -> Result<(), i32> {
+    let mut saw_error = false;
+    for segment in segments {
+        let mut stream = connect(instance_selector)?;
+
+        stream
+            .write_all(format!("{}\n", segment).as_bytes())
+            .map_err(|_| {
+                eprintln!("Error: Failed to send command to daemon.");
+                EXIT_CONNECTION_ERROR
+            })?;
+
+        stream.shutdown(std::net::Shutdown::Write).map_err(|_| {
+            eprintln!("Error: Failed to complete command transmission.");
+            EXIT_CONNECTION_ERROR
+        })?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).map_err(|_| {
+            eprintln!("Error: Failed to read response from daemon.");
+            EXIT_CONNECTION_ERROR
+        })?;
+
+        if handle_response(&response, json).is_err() {
+            saw_error = true;
+        }
+    }
+
+    if saw_error {
+        Err(EXIT_COMMAND_ERROR)
+    } else {
+        Ok(())
+    }
+}
+
+// Keep the connection open after `subscribe`, relaying daemon events to
+// stdout as they arrive. This is the client-side counterpart to the
+// persistent IPC loop bars like waybar register for. Returns once the
+// daemon closes the socket (or the process is interrupted).
+fn run_subscribe(stream: &mut UnixStream, filters: &[String]) -> Result<(), i32> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|_| {
+        eprintln!("Error: Failed to duplicate socket handle.");
+        EXIT_CONNECTION_ERROR
+    })?);
+    let mut stdout = io::stdout();
+
+    loop {
+        let mut line = String::new();
+        let bytes_read = reader.read_line(&mut line).map_err(|_| {
+            eprintln!("Error: Failed to read event from daemon.");
+            EXIT_CONNECTION_ERROR
+        })?;
+        if bytes_read == 0 {
+            // Daemon closed the socket.
+            return Ok(());
+        }
+
+        let event_name = line.split_whitespace().next().unwrap_or("");
+        if filters.is_empty() || filters.iter().any(|filter| filter == event_name) {
+            print!("{}", line);
+            stdout.flush().ok();
+        }
+    }
+}
+
 fn main() {
     exit(run().err().unwrap_or(EXIT_SUCCESS));
 }
@@ -1,8 +1,11 @@
 use std::env;
-use std::io::{Write};
+use std::io::{Read, Write};
 use std::os::unix::net::UnixStream;
 use std::process;
 
+// Exit codes matching pyprland/models.py ExitCode
+const EXIT_COMMAND_ERROR: i32 = 4;
+
 fn main() {
     // If no argument passed, just exit
     let args: Vec<String> = env::args().collect();
@@ -70,4 +73,30 @@ wall                 <next|clear> skip the current background image or stop disp
         eprintln!("Error writing to socket: {}", err);
         process::exit(1);
     });
+
+    // Signal end of message
+    conn.shutdown(std::net::Shutdown::Write).unwrap_or_else(|err| {
+        eprintln!("Error completing command transmission: {}", err);
+        process::exit(1);
+    });
+
+    // Read the daemon's response
+    let mut response = String::new();
+    conn.read_to_string(&mut response).unwrap_or_else(|err| {
+        eprintln!("Error reading response from socket: {}", err);
+        process::exit(1);
+    });
+
+    // Parse response and determine exit code
+    if let Some(error_msg) = response.strip_prefix("ERROR: ") {
+        eprintln!("Error: {}", error_msg.trim_end());
+        process::exit(EXIT_COMMAND_ERROR);
+    } else if let Some(rest) = response.strip_prefix("OK") {
+        let output = rest.trim_start();
+        if !output.is_empty() {
+            print!("{}", output);
+        }
+    } else if !response.is_empty() {
+        println!("{}", response.trim_end_matches('\n'));
+    }
 }